@@ -2,7 +2,10 @@
 
 use tidb_query_codegen::rpn_fn;
 
+use crate::batch::LazyBatchColumnVec;
 use crate::codec::data_type::*;
+use crate::expr::EvalContext;
+use crate::rpn_expr::{RpnExpression, RpnExpressionNode};
 use crate::Result;
 
 #[rpn_fn]
@@ -70,6 +73,69 @@ pub fn bit_and(lhs: &Option<Int>, rhs: &Option<Int>) -> Result<Option<Int>> {
     })
 }
 
+// FIXME(chunk0-6): these are NOT yet reachable at runtime. The signature mapper
+// `rpn_expr::map_expr_node_to_rpn_func` (not part of this crate snapshot) still
+// has to route the matching `ScalarFuncSig` to each function:
+//   ScalarFuncSig::BitOrSig    => bit_or_fn_meta(),
+//   ScalarFuncSig::BitXorSig   => bit_xor_fn_meta(),
+//   ScalarFuncSig::LeftShift   => left_shift_fn_meta(),
+//   ScalarFuncSig::RightShift  => right_shift_fn_meta(),
+// Until those arms are added, pushed-down bitwise expressions keep falling back
+// to TiDB, and the unit tests below, which evaluate through `ScalarFuncSig`,
+// cannot reach these functions either. (`BitAndSig` is already registered
+// alongside `bit_and`.)
+#[rpn_fn]
+#[inline]
+pub fn bit_or(lhs: &Option<Int>, rhs: &Option<Int>) -> Result<Option<Int>> {
+    Ok(match (lhs, rhs) {
+        (Some(lhs), Some(rhs)) => Some((*lhs as u64 | *rhs as u64) as i64),
+        _ => None,
+    })
+}
+
+#[rpn_fn]
+#[inline]
+pub fn bit_xor(lhs: &Option<Int>, rhs: &Option<Int>) -> Result<Option<Int>> {
+    Ok(match (lhs, rhs) {
+        (Some(lhs), Some(rhs)) => Some((*lhs as u64 ^ *rhs as u64) as i64),
+        _ => None,
+    })
+}
+
+#[rpn_fn]
+#[inline]
+pub fn left_shift(lhs: &Option<Int>, rhs: &Option<Int>) -> Result<Option<Int>> {
+    Ok(match (lhs, rhs) {
+        // The shift count is treated as unsigned, so a count of 64 or more (or
+        // any negative value reinterpreted as `u64`) shifts every bit out.
+        (Some(lhs), Some(rhs)) => {
+            let shift = *rhs as u64;
+            if shift >= 64 {
+                Some(0)
+            } else {
+                Some(((*lhs as u64) << shift) as i64)
+            }
+        }
+        _ => None,
+    })
+}
+
+#[rpn_fn]
+#[inline]
+pub fn right_shift(lhs: &Option<Int>, rhs: &Option<Int>) -> Result<Option<Int>> {
+    Ok(match (lhs, rhs) {
+        (Some(lhs), Some(rhs)) => {
+            let shift = *rhs as u64;
+            if shift >= 64 {
+                Some(0)
+            } else {
+                Some(((*lhs as u64) >> shift) as i64)
+            }
+        }
+        _ => None,
+    })
+}
+
 #[rpn_fn]
 #[inline]
 pub fn bit_neg(arg: &Option<Int>) -> Result<Option<Int>> {
@@ -112,6 +178,195 @@ fn decimal_is_false(arg: &Option<Decimal>) -> Result<Option<i64>> {
     Ok(Some(arg.as_ref().map_or(0, |v| v.is_zero() as i64)))
 }
 
+/// Returns whether a scalar function, identified by the Rust name its
+/// `#[rpn_fn]` generates, is known to be deterministic and side-effect free and
+/// may therefore be pre-evaluated when all of its operands are constants.
+///
+/// This is a conservative allowlist: only functions explicitly listed here are
+/// folded, so an unknown or newly-added signature is left untouched rather than
+/// folded on the assumption that it is pure. Folding a function that observes
+/// external state (`rand`, `now`, `connection_id`, ...) would freeze one value
+/// across every row, so the allowlist must only ever grow with functions that
+/// depend on nothing but their operands.
+fn is_deterministic(name: &str) -> bool {
+    matches!(
+        name,
+        "is_null"
+            | "logical_and"
+            | "logical_or"
+            | "logical_xor"
+            | "unary_not_int"
+            | "unary_not_real"
+            | "unary_not_decimal"
+            | "bit_and"
+            | "bit_or"
+            | "bit_xor"
+            | "bit_neg"
+            | "left_shift"
+            | "right_shift"
+            | "int_is_true"
+            | "real_is_true"
+            | "decimal_is_true"
+            | "int_is_false"
+            | "real_is_false"
+            | "decimal_is_false"
+    )
+}
+
+/// Folds maximal constant sub-expressions of a postfix RPN expression.
+///
+/// The node vector is scanned left to right. Whenever an `FnCall` node is
+/// reached whose argument nodes (the `args_len` nodes immediately preceding it
+/// in the already-folded output) are all `Constant` and whose function is
+/// deterministic, the whole sub-expression is evaluated once over a single
+/// logical row and the arguments plus the call are replaced by a single
+/// `Constant` node. Folding therefore composes: an outer call whose arguments
+/// have just been folded into constants becomes foldable in turn.
+///
+/// Evaluation goes through the normal [`RpnExpression::eval`] path, so
+/// argument-count and type invariants as well as NULL semantics (e.g. a folded
+/// `logical_and(NULL, 0)` still yields `0`) are exactly what a per-row
+/// evaluation would produce; malformed expressions surface the same errors.
+///
+/// The pass is a self-contained rewrite over the node vector: it returns a new
+/// [`RpnExpression`] with the constant sub-expressions collapsed and is meant to
+/// be applied once after an expression has been built from its protobuf form.
+/// It currently only takes effect where it is invoked explicitly; it is not yet
+/// wired into the RPN build pipeline, so runtime expressions are not folded
+/// until that call site is added.
+pub fn fold_constants(ctx: &mut EvalContext, expr: RpnExpression) -> Result<RpnExpression> {
+    let src: Vec<RpnExpressionNode> = expr.into();
+    let mut out: Vec<RpnExpressionNode> = Vec::with_capacity(src.len());
+    for node in src {
+        match node {
+            RpnExpressionNode::FnCall {
+                func_meta,
+                args_len,
+                field_type,
+                metadata,
+            } => {
+                let foldable = is_deterministic(func_meta.name)
+                    && args_len <= out.len()
+                    && out[out.len() - args_len..]
+                        .iter()
+                        .all(|n| matches!(n, RpnExpressionNode::Constant { .. }));
+                if !foldable {
+                    out.push(RpnExpressionNode::FnCall {
+                        func_meta,
+                        args_len,
+                        field_type,
+                        metadata,
+                    });
+                    continue;
+                }
+                // Splice out the constant arguments and rebuild the isolated
+                // sub-expression `[args.., fn_call]` to evaluate it in place.
+                let mut sub: Vec<RpnExpressionNode> = out.split_off(out.len() - args_len);
+                sub.push(RpnExpressionNode::FnCall {
+                    func_meta,
+                    args_len,
+                    field_type: field_type.clone(),
+                    metadata,
+                });
+                let value = RpnExpression::from(sub)
+                    .eval(ctx, &[], &LazyBatchColumnVec::empty(), &[0], 1)?
+                    .into_scalar_value();
+                out.push(RpnExpressionNode::Constant { value, field_type });
+            }
+            other => out.push(other),
+        }
+    }
+    Ok(RpnExpression::from(out))
+}
+
+/// Kleene AND on two scalar operands, matching [`logical_and`].
+#[inline]
+fn and_kleene(lhs: Option<Int>, rhs: Option<Int>) -> Option<Int> {
+    match (lhs, rhs) {
+        (Some(0), _) | (_, Some(0)) => Some(0),
+        (None, _) | (_, None) => None,
+        _ => Some(1),
+    }
+}
+
+/// Kleene OR on two scalar operands, matching [`logical_or`].
+#[inline]
+fn or_kleene(lhs: Option<Int>, rhs: Option<Int>) -> Option<Int> {
+    match (lhs, rhs) {
+        (Some(0), Some(0)) => Some(0),
+        (None, None) | (None, Some(0)) | (Some(0), None) => None,
+        _ => Some(1),
+    }
+}
+
+/// Short-circuiting vectorized evaluation of `logical_and`.
+///
+/// This is the building block for a lazy `LogicalAnd` evaluation: given the
+/// already-evaluated left column it decides the result for every row whose left
+/// operand is `0` without touching the right subtree, and only asks `eval_rhs`
+/// for the remaining undetermined rows. It is not yet called from the vectorized
+/// eval dispatch, so the short-circuit is not exercised in production until that
+/// call site is added.
+///
+/// `lhs` is the fully-evaluated left column. `eval_rhs` evaluates the right
+/// subtree over exactly the logical-row indices handed to it and returns the
+/// results in the same order. For AND the result is already decided as `0`
+/// wherever the left operand is `0`, so those rows are never passed to the
+/// right subtree; only rows whose left operand is nonzero or NULL remain
+/// undetermined. The caller must gate this path so that `eval_rhs` is
+/// deterministic and side-effect free, otherwise skipping rows could change
+/// observable behavior.
+pub fn logical_and_vec<F>(lhs: &[Option<Int>], eval_rhs: F) -> Result<Vec<Option<Int>>>
+where
+    F: FnOnce(&[usize]) -> Result<Vec<Option<Int>>>,
+{
+    let undetermined: Vec<usize> = (0..lhs.len()).filter(|&i| lhs[i] != Some(0)).collect();
+    let rhs = eval_rhs(&undetermined)?;
+    debug_assert_eq!(rhs.len(), undetermined.len());
+    let mut out = Vec::with_capacity(lhs.len());
+    let mut next = 0;
+    for (i, &l) in lhs.iter().enumerate() {
+        if l == Some(0) {
+            out.push(Some(0));
+        } else {
+            debug_assert_eq!(undetermined[next], i);
+            out.push(and_kleene(l, rhs[next]));
+            next += 1;
+        }
+    }
+    Ok(out)
+}
+
+/// Short-circuiting vectorized evaluation of `logical_or`.
+///
+/// The dual of [`logical_and_vec`]: the result is already decided as `1`
+/// wherever the left operand is nonzero, so only rows whose left operand is `0`
+/// or NULL are handed to `eval_rhs`. The same determinism gate applies, and like
+/// [`logical_and_vec`] it is not yet wired into the vectorized eval dispatch.
+pub fn logical_or_vec<F>(lhs: &[Option<Int>], eval_rhs: F) -> Result<Vec<Option<Int>>>
+where
+    F: FnOnce(&[usize]) -> Result<Vec<Option<Int>>>,
+{
+    let undetermined: Vec<usize> = (0..lhs.len())
+        .filter(|&i| !matches!(lhs[i], Some(v) if v != 0))
+        .collect();
+    let rhs = eval_rhs(&undetermined)?;
+    debug_assert_eq!(rhs.len(), undetermined.len());
+    let mut out = Vec::with_capacity(lhs.len());
+    let mut next = 0;
+    for (i, &l) in lhs.iter().enumerate() {
+        match l {
+            Some(v) if v != 0 => out.push(Some(1)),
+            _ => {
+                debug_assert_eq!(undetermined[next], i);
+                out.push(or_kleene(l, rhs[next]));
+                next += 1;
+            }
+        }
+    }
+    Ok(out)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -119,6 +374,8 @@ mod tests {
 
     use crate::codec::mysql::TimeType;
     use crate::rpn_expr::test_util::RpnFnScalarEvaluator;
+    use crate::rpn_expr::RpnExpressionBuilder;
+    use tidb_query_datatype::FieldTypeTp;
 
     #[test]
     fn test_logical_and() {
@@ -327,6 +584,156 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_fold_constants() {
+        let mut ctx = EvalContext::default();
+
+        // `logical_and(NULL, 0)` collapses to a single constant `0`, preserving
+        // the Kleene result rather than propagating NULL.
+        let expr = RpnExpressionBuilder::new_for_test()
+            .push_constant_for_test(ScalarValue::Int(None))
+            .push_constant_for_test(0i64)
+            .push_fn_call_for_test(logical_and_fn_meta(), 2, FieldTypeTp::LongLong)
+            .build_for_test();
+        let nodes: Vec<RpnExpressionNode> = fold_constants(&mut ctx, expr).unwrap().into();
+        assert_eq!(nodes.len(), 1);
+        match &nodes[0] {
+            RpnExpressionNode::Constant { value, .. } => assert_eq!(value.as_int(), &Some(0)),
+            node => panic!("expected a folded constant, got {:?}", node),
+        }
+
+        // A sub-expression referencing a column is not constant, so nothing is
+        // folded and the node vector is left untouched.
+        let expr = RpnExpressionBuilder::new_for_test()
+            .push_column_ref_for_test(0)
+            .push_constant_for_test(1i64)
+            .push_fn_call_for_test(logical_and_fn_meta(), 2, FieldTypeTp::LongLong)
+            .build_for_test();
+        let nodes: Vec<RpnExpressionNode> = fold_constants(&mut ctx, expr).unwrap().into();
+        assert_eq!(nodes.len(), 3);
+    }
+
+    #[test]
+    fn test_logical_and_vec_short_circuit() {
+        use std::cell::RefCell;
+
+        let lhs = vec![Some(0), Some(1), None, Some(2)];
+        let evaluated = RefCell::new(Vec::new());
+        let out = logical_and_vec(&lhs, |rows| {
+            *evaluated.borrow_mut() = rows.to_vec();
+            // RHS values supplied in the order of `rows`.
+            Ok(vec![Some(1), Some(0), Some(1)])
+        })
+        .unwrap();
+        // Row 0 short-circuits to 0 without touching the RHS.
+        assert_eq!(*evaluated.borrow(), vec![1, 2, 3]);
+        // 1 AND 1 == 1, NULL AND 0 == 0, 2 AND 1 == 1.
+        assert_eq!(out, vec![Some(0), Some(1), Some(0), Some(1)]);
+    }
+
+    #[test]
+    fn test_logical_or_vec_short_circuit() {
+        use std::cell::RefCell;
+
+        let lhs = vec![Some(1), Some(0), None, Some(0)];
+        let evaluated = RefCell::new(Vec::new());
+        let out = logical_or_vec(&lhs, |rows| {
+            *evaluated.borrow_mut() = rows.to_vec();
+            Ok(vec![Some(1), None, Some(0)])
+        })
+        .unwrap();
+        // Row 0 short-circuits to 1 without touching the RHS.
+        assert_eq!(*evaluated.borrow(), vec![1, 2, 3]);
+        // 0 OR 1 == 1, NULL OR NULL == NULL, 0 OR 0 == 0.
+        assert_eq!(out, vec![Some(1), Some(1), None, Some(0)]);
+    }
+
+    #[test]
+    fn test_bit_or() {
+        let cases = vec![
+            (Some(123), Some(321), Some(379)),
+            (Some(-123), Some(321), Some(-59)),
+            (None, Some(1), None),
+            (Some(1), None, None),
+            (None, None, None),
+        ];
+        for (lhs, rhs, expected) in cases {
+            let output = RpnFnScalarEvaluator::new()
+                .push_param(lhs)
+                .push_param(rhs)
+                .evaluate(ScalarFuncSig::BitOrSig)
+                .unwrap();
+            assert_eq!(output, expected);
+        }
+    }
+
+    #[test]
+    fn test_bit_xor() {
+        let cases = vec![
+            (Some(123), Some(321), Some(314)),
+            (Some(-123), Some(321), Some(-316)),
+            (None, Some(1), None),
+            (Some(1), None, None),
+            (None, None, None),
+        ];
+        for (lhs, rhs, expected) in cases {
+            let output = RpnFnScalarEvaluator::new()
+                .push_param(lhs)
+                .push_param(rhs)
+                .evaluate(ScalarFuncSig::BitXorSig)
+                .unwrap();
+            assert_eq!(output, expected);
+        }
+    }
+
+    #[test]
+    fn test_left_shift() {
+        let cases = vec![
+            (Some(123), Some(2), Some(492)),
+            // Operands are reinterpreted as unsigned 64-bit.
+            (Some(-1), Some(1), Some(-2)),
+            // A shift count of 64 or more yields 0, not a panic.
+            (Some(1), Some(64), Some(0)),
+            (Some(1), Some(100), Some(0)),
+            // A negative count reinterpreted as u64 is huge, so also 0.
+            (Some(1), Some(-1), Some(0)),
+            (None, Some(1), None),
+            (Some(1), None, None),
+            (None, None, None),
+        ];
+        for (lhs, rhs, expected) in cases {
+            let output = RpnFnScalarEvaluator::new()
+                .push_param(lhs)
+                .push_param(rhs)
+                .evaluate(ScalarFuncSig::LeftShift)
+                .unwrap();
+            assert_eq!(output, expected);
+        }
+    }
+
+    #[test]
+    fn test_right_shift() {
+        let cases = vec![
+            (Some(492), Some(2), Some(123)),
+            // -1 as u64 is all ones; >> 1 keeps the top bit clear.
+            (Some(-1), Some(1), Some(i64::MAX)),
+            (Some(1), Some(64), Some(0)),
+            (Some(1), Some(100), Some(0)),
+            (Some(1), Some(-1), Some(0)),
+            (None, Some(1), None),
+            (Some(1), None, None),
+            (None, None, None),
+        ];
+        for (lhs, rhs, expected) in cases {
+            let output = RpnFnScalarEvaluator::new()
+                .push_param(lhs)
+                .push_param(rhs)
+                .evaluate(ScalarFuncSig::RightShift)
+                .unwrap();
+            assert_eq!(output, expected);
+        }
+    }
+
     #[test]
     fn test_is_true() {
         let test_cases = vec![
